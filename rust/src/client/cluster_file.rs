@@ -0,0 +1,217 @@
+//! Parses FoundationDB's cluster file format:
+//! `description:id@host:port,host:port,...`.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+/// Why a cluster file couldn't be loaded, distinct from a generic I/O error
+/// so callers can tell "no cluster configured" apart from "cluster file is
+/// corrupt".
+#[derive(Debug)]
+pub enum ClusterFileError {
+    NotFound(PathBuf),
+    Unreadable(PathBuf, std::io::Error),
+    Empty(PathBuf),
+    Malformed { path: PathBuf, reason: String },
+}
+
+impl fmt::Display for ClusterFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClusterFileError::NotFound(p) => write!(f, "cluster file not found: {}", p.display()),
+            ClusterFileError::Unreadable(p, e) => {
+                write!(f, "cluster file {} is unreadable: {e}", p.display())
+            }
+            ClusterFileError::Empty(p) => write!(f, "cluster file {} is empty", p.display()),
+            ClusterFileError::Malformed { path, reason } => {
+                write!(f, "cluster file {} is malformed: {reason}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClusterFileError {}
+
+impl From<ClusterFileError> for crate::flow::Error {
+    fn from(e: ClusterFileError) -> Self {
+        crate::flow::Error::Other(e.to_string())
+    }
+}
+
+/// A parsed cluster file: the cluster's description, its coordinator-set id,
+/// and the coordinators themselves.
+#[derive(Debug, Clone)]
+pub struct ClusterFile {
+    pub description: String,
+    pub id: String,
+    pub coordinators: Vec<SocketAddr>,
+}
+
+impl ClusterFile {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ClusterFileError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(ClusterFileError::NotFound(path.to_path_buf()));
+        }
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ClusterFileError::Unreadable(path.to_path_buf(), e))?;
+        let contents = contents.trim();
+        if contents.is_empty() {
+            return Err(ClusterFileError::Empty(path.to_path_buf()));
+        }
+        Self::parse(contents).map_err(|reason| ClusterFileError::Malformed {
+            path: path.to_path_buf(),
+            reason,
+        })
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let (desc_id, coords) = contents
+            .split_once('@')
+            .ok_or_else(|| "missing '@' separating description:id from coordinators".to_string())?;
+        let (description, id) = desc_id
+            .split_once(':')
+            .ok_or_else(|| "missing ':' separating description from id".to_string())?;
+        if description.is_empty() || id.is_empty() {
+            return Err("description and id must both be non-empty".to_string());
+        }
+
+        let coordinators = coords
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                s.parse::<SocketAddr>()
+                    .map_err(|e| format!("invalid coordinator address {s:?}: {e}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if coordinators.is_empty() {
+            return Err("no coordinators listed".to_string());
+        }
+
+        Ok(ClusterFile {
+            description: description.to_string(),
+            id: id.to_string(),
+            coordinators,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// A path under the system temp dir unique to this test process/call, so
+    /// tests running concurrently don't collide.
+    fn scratch_path(name: &str) -> PathBuf {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir().join(format!(
+            "fdb-cluster-file-test-{}-{n}-{name}",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn missing_file_is_not_found() {
+        let path = scratch_path("missing");
+        match ClusterFile::load(&path) {
+            Err(ClusterFileError::NotFound(p)) => assert_eq!(p, path),
+            other => panic!("expected NotFound, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn empty_file_is_rejected() {
+        let path = scratch_path("empty");
+        std::fs::write(&path, "   \n").unwrap();
+        let result = ClusterFile::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(ClusterFileError::Empty(p)) => assert_eq!(p, path),
+            other => panic!("expected Empty, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn missing_at_sign_is_malformed() {
+        let path = scratch_path("no-at-sign");
+        std::fs::write(&path, "description:id").unwrap();
+        let result = ClusterFile::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(ClusterFileError::Malformed { .. })));
+    }
+
+    #[test]
+    fn missing_colon_is_malformed() {
+        let path = scratch_path("no-colon");
+        std::fs::write(&path, "descriptionid@127.0.0.1:4500").unwrap();
+        let result = ClusterFile::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(ClusterFileError::Malformed { .. })));
+    }
+
+    #[test]
+    fn empty_description_or_id_is_malformed() {
+        let path = scratch_path("empty-id");
+        std::fs::write(&path, ":id@127.0.0.1:4500").unwrap();
+        let result = ClusterFile::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(ClusterFileError::Malformed { .. })));
+    }
+
+    #[test]
+    fn invalid_coordinator_address_is_malformed() {
+        let path = scratch_path("bad-addr");
+        std::fs::write(&path, "description:id@not-an-address").unwrap();
+        let result = ClusterFile::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(ClusterFileError::Malformed { .. })));
+    }
+
+    #[test]
+    fn no_coordinators_is_malformed() {
+        let path = scratch_path("no-coordinators");
+        std::fs::write(&path, "description:id@").unwrap();
+        let result = ClusterFile::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(ClusterFileError::Malformed { .. })));
+    }
+
+    #[test]
+    fn parses_a_well_formed_cluster_file() {
+        let path = scratch_path("well-formed");
+        std::fs::write(
+            &path,
+            "test_cluster:abcdefg@127.0.0.1:4500,127.0.0.1:4501\n",
+        )
+        .unwrap();
+        let result = ClusterFile::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let cluster = result.unwrap();
+        assert_eq!(cluster.description, "test_cluster");
+        assert_eq!(cluster.id, "abcdefg");
+        assert_eq!(
+            cluster.coordinators,
+            vec![
+                "127.0.0.1:4500".parse().unwrap(),
+                "127.0.0.1:4501".parse().unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn cluster_file_error_converts_to_flow_error() {
+        let err: crate::flow::Error = ClusterFileError::Empty(PathBuf::from("/tmp/x")).into();
+        assert!(matches!(err, crate::flow::Error::Other(_)));
+    }
+}
@@ -0,0 +1,34 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::cluster_file::ClusterFile;
+use super::transaction::Transaction;
+use crate::fdbserver::grv_master::{GrvMaster, GrvMasterConfig};
+use crate::flow::io::tokio_backend::TokioConnection;
+use crate::flow::Result;
+
+/// A handle to a cluster, opened from a cluster file.
+pub struct Database {
+    #[allow(dead_code)]
+    cluster: ClusterFile,
+    grv: GrvMaster,
+}
+
+/// Opens a database from a cluster file at `cluster_file`, in the
+/// `description:id@host:port,...` format.
+pub fn open_database(cluster_file: &Path) -> Result<Database> {
+    let cluster = ClusterFile::load(cluster_file)?;
+    let grv = GrvMaster::new(GrvMasterConfig {
+        network: Arc::new(TokioConnection::new()),
+        batch_window: Duration::from_millis(1),
+        max_batch_size: 1000,
+    });
+    Ok(Database { cluster, grv })
+}
+
+impl Database {
+    pub fn create_transaction(&self) -> Transaction {
+        Transaction::new(self.grv.clone())
+    }
+}
@@ -0,0 +1,12 @@
+//! A `flow`-based client surface for talking to a cluster: open a
+//! [`Database`] from a cluster file, start [`Transaction`]s, and read/write
+//! keys and ranges. Modeled on the shape of the established Tokio
+//! FoundationDB client.
+
+mod cluster_file;
+mod database;
+mod transaction;
+
+pub use cluster_file::{ClusterFile, ClusterFileError};
+pub use database::{open_database, Database};
+pub use transaction::{GetRangeOptions, Transaction};
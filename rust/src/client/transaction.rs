@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::fdbserver::grv_master::{GrvMaster, Priority, Version};
+use crate::flow::{Error, Result};
+
+/// Options controlling a [`Transaction::get_range`] call.
+#[derive(Debug, Clone, Default)]
+pub struct GetRangeOptions {
+    pub limit: Option<usize>,
+    pub reverse: bool,
+}
+
+enum Mutation {
+    Set(Vec<u8>, Vec<u8>),
+    ClearRange(Vec<u8>, Vec<u8>),
+}
+
+/// A single transaction against a [`super::Database`]. Reads lazily fetch a
+/// read version from the cluster's GRV master on first use; writes are
+/// buffered locally until [`Transaction::commit`]. `get`/`get_range` apply
+/// the buffer first (read-your-own-writes), since there's no storage server
+/// in this crate yet for them to fall back to; [`Transaction::commit`]
+/// reports that plainly rather than pretending to have sent the mutations
+/// anywhere.
+pub struct Transaction {
+    grv: GrvMaster,
+    read_version: Mutex<Option<Version>>,
+    writes: Mutex<Vec<Mutation>>,
+}
+
+impl Transaction {
+    pub(crate) fn new(grv: GrvMaster) -> Self {
+        Transaction {
+            grv,
+            read_version: Mutex::new(None),
+            writes: Mutex::new(Vec::new()),
+        }
+    }
+
+    async fn ensure_read_version(&self) -> Result<Version> {
+        if let Some(v) = *self.read_version.lock().unwrap() {
+            return Ok(v);
+        }
+        let v = self.grv.get_read_version(Priority::Default).await?;
+        *self.read_version.lock().unwrap() = Some(v);
+        Ok(v)
+    }
+
+    pub async fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.ensure_read_version().await?;
+        // No storage server to fall back to yet; the only data a
+        // transaction can ever see is what it buffered itself.
+        Ok(Self::apply_writes(&self.writes.lock().unwrap(), key))
+    }
+
+    /// Replays the buffered mutations in commit order and returns what `key`
+    /// would read as, or `None` if nothing in the buffer touches it.
+    fn apply_writes(writes: &[Mutation], key: &[u8]) -> Option<Vec<u8>> {
+        let mut value = None;
+        for mutation in writes {
+            match mutation {
+                Mutation::Set(k, v) if k.as_slice() == key => value = Some(v.clone()),
+                Mutation::ClearRange(begin, end)
+                    if begin.as_slice() <= key && key < end.as_slice() =>
+                {
+                    value = None;
+                }
+                _ => {}
+            }
+        }
+        value
+    }
+
+    pub fn set(&self, key: &[u8], value: &[u8]) {
+        self.writes
+            .lock()
+            .unwrap()
+            .push(Mutation::Set(key.to_vec(), value.to_vec()));
+    }
+
+    pub fn clear_range(&self, begin: &[u8], end: &[u8]) {
+        self.writes
+            .lock()
+            .unwrap()
+            .push(Mutation::ClearRange(begin.to_vec(), end.to_vec()));
+    }
+
+    pub async fn get_range(
+        &self,
+        begin: &[u8],
+        end: &[u8],
+        options: GetRangeOptions,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.ensure_read_version().await?;
+        // Same reasoning as `get`: replay the buffer in commit order over
+        // [begin, end) since there's nothing else to read from yet.
+        let mut merged: BTreeMap<Vec<u8>, Vec<u8>> = BTreeMap::new();
+        for mutation in self.writes.lock().unwrap().iter() {
+            match mutation {
+                Mutation::Set(k, v) if begin <= k.as_slice() && k.as_slice() < end => {
+                    merged.insert(k.clone(), v.clone());
+                }
+                Mutation::ClearRange(clear_begin, clear_end) => {
+                    let lo = clear_begin.as_slice().max(begin);
+                    let hi = clear_end.as_slice().min(end);
+                    if lo < hi {
+                        merged.retain(|k, _| !(lo <= k.as_slice() && k.as_slice() < hi));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut results: Vec<_> = merged.into_iter().collect();
+        if options.reverse {
+            results.reverse();
+        }
+        if let Some(limit) = options.limit {
+            results.truncate(limit);
+        }
+        Ok(results)
+    }
+
+    /// Sends the buffered mutations to the cluster's resolver and clears the
+    /// buffer on success.
+    ///
+    /// There's no resolver wired up yet, so a transaction with pending
+    /// writes can't actually be committed; report that plainly instead of
+    /// silently dropping the mutations and claiming success.
+    pub async fn commit(&self) -> Result<()> {
+        self.ensure_read_version().await?;
+        let pending = self.writes.lock().unwrap().len();
+        if pending == 0 {
+            return Ok(());
+        }
+        Err(Error::Other(format!(
+            "commit not implemented: no resolver to send {pending} buffered mutation(s) to"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fdbserver::grv_master::GrvMasterConfig;
+    use crate::flow::io::mem::MemConnection;
+    use crate::flow::sim::Sim;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn txn() -> Transaction {
+        let (server, _client) = MemConnection::pair();
+        let grv = GrvMaster::new(GrvMasterConfig {
+            network: Arc::new(server),
+            batch_window: Duration::from_millis(1),
+            max_batch_size: 10,
+        });
+        Transaction::new(grv)
+    }
+
+    #[test]
+    fn get_sees_its_own_buffered_write() {
+        Sim::run_with_seed(1, async {
+            let txn = txn();
+            assert_eq!(txn.get(b"k").await.unwrap(), None);
+            txn.set(b"k", b"v");
+            assert_eq!(txn.get(b"k").await.unwrap(), Some(b"v".to_vec()));
+        });
+    }
+
+    #[test]
+    fn clear_range_hides_a_previously_buffered_write() {
+        Sim::run_with_seed(2, async {
+            let txn = txn();
+            txn.set(b"k", b"v");
+            txn.clear_range(b"a", b"z");
+            assert_eq!(txn.get(b"k").await.unwrap(), None);
+        });
+    }
+
+    #[test]
+    fn get_range_merges_buffered_writes_in_commit_order() {
+        Sim::run_with_seed(3, async {
+            let txn = txn();
+            txn.set(b"a", b"1");
+            txn.set(b"b", b"2");
+            txn.set(b"c", b"3");
+            txn.clear_range(b"b", b"c");
+
+            let all = txn
+                .get_range(b"a", b"z", GetRangeOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(
+                all,
+                vec![(b"a".to_vec(), b"1".to_vec()), (b"c".to_vec(), b"3".to_vec())]
+            );
+
+            let reversed = txn
+                .get_range(
+                    b"a",
+                    b"z",
+                    GetRangeOptions {
+                        reverse: true,
+                        limit: Some(1),
+                    },
+                )
+                .await
+                .unwrap();
+            assert_eq!(reversed, vec![(b"c".to_vec(), b"3".to_vec())]);
+        });
+    }
+
+    #[test]
+    fn commit_with_no_writes_succeeds() {
+        Sim::run_with_seed(4, async {
+            let txn = txn();
+            assert!(txn.commit().await.is_ok());
+        });
+    }
+
+    #[test]
+    fn commit_with_pending_writes_reports_not_implemented_instead_of_dropping_them() {
+        Sim::run_with_seed(5, async {
+            let txn = txn();
+            txn.set(b"k", b"v");
+            assert!(txn.commit().await.is_err());
+            // The failed commit must not have silently discarded the write.
+            assert_eq!(txn.get(b"k").await.unwrap(), Some(b"v".to_vec()));
+        });
+    }
+}
@@ -0,0 +1,5 @@
+//! Server-side roles that make up an `fdbserver` process. Each role is
+//! written against the `flow::io` facade so the same logic runs on a real
+//! cluster or under `flow::sim`.
+
+pub mod grv_master;
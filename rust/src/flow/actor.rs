@@ -0,0 +1,320 @@
+//! FoundationDB's actor-model concurrency primitives: a `Promise`/`Future`
+//! pair that can be fulfilled at most once, `PromiseStream`/`FutureStream`
+//! for multi-value actor messaging, and `choose`/`when` combinators that
+//! await the first of several futures while cancelling the rest.
+//!
+//! Everything here is built on plain `std::task` wakers rather than on any
+//! particular executor, so under [`super::sim`] a promise's fulfillment (and
+//! a cancelled future's drop) happens exactly when the virtual clock wakes
+//! the task that triggers it, making the whole thing deterministic for a
+//! given seed.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::{poll_fn, Future as StdFuture};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+struct PromiseShared<T> {
+    value: Option<T>,
+    wakers: Vec<Waker>,
+    abandoned: bool,
+}
+
+/// The sending half of a promise pair: fulfils the paired [`Future`] at most
+/// once.
+pub struct Promise<T> {
+    shared: Arc<Mutex<PromiseShared<T>>>,
+}
+
+/// The awaitable half of a promise pair. Cloneable: every clone observes the
+/// same eventual value.
+pub struct Future<T> {
+    shared: Arc<Mutex<PromiseShared<T>>>,
+}
+
+impl<T> Clone for Future<T> {
+    fn clone(&self) -> Self {
+        Future {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+/// Creates a fresh `Promise`/`Future` pair.
+pub fn promise<T>() -> (Promise<T>, Future<T>) {
+    let shared = Arc::new(Mutex::new(PromiseShared {
+        value: None,
+        wakers: Vec::new(),
+        abandoned: false,
+    }));
+    (
+        Promise {
+            shared: shared.clone(),
+        },
+        Future { shared },
+    )
+}
+
+impl<T> Promise<T> {
+    /// Fulfils the promise, waking every waiting [`Future`]. Fulfilling a
+    /// promise twice is a bug in the caller and panics, the same as sending
+    /// twice on a oneshot channel would be rejected.
+    pub fn send(self, value: T) {
+        let mut shared = self.shared.lock().unwrap();
+        assert!(shared.value.is_none(), "promise fulfilled twice");
+        shared.value = Some(value);
+        for waker in shared.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for Promise<T> {
+    fn drop(&mut self) {
+        // A promise dropped without being fulfilled would otherwise leave
+        // every waiting `Future` parked forever; wake them so they can
+        // observe the broken promise instead of hanging.
+        let mut shared = self.shared.lock().unwrap();
+        if shared.value.is_none() {
+            shared.abandoned = true;
+            for waker in shared.wakers.drain(..) {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// A [`Future`]'s [`Promise`] was dropped without being fulfilled — the
+/// cooperative-actor equivalent of a broken oneshot channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "promise dropped without being fulfilled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+impl<T: Clone> StdFuture for Future<T> {
+    type Output = Result<T, Cancelled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap();
+        match &shared.value {
+            Some(value) => Poll::Ready(Ok(value.clone())),
+            None if shared.abandoned => Poll::Ready(Err(Cancelled)),
+            None => {
+                shared.wakers.push(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+struct StreamShared<T> {
+    queue: VecDeque<T>,
+    waker: Option<Waker>,
+    closed: bool,
+    // Tracked explicitly rather than via `Arc::strong_count`, since the
+    // paired `FutureStream` holds its own permanent strong reference to the
+    // same `Arc` and would otherwise keep the count from ever reaching one.
+    senders: usize,
+}
+
+/// The sending half of a [`FutureStream`]. Cloneable: every clone pushes
+/// into the same queue, the way actors fan messages into a shared mailbox.
+pub struct PromiseStream<T> {
+    shared: Arc<Mutex<StreamShared<T>>>,
+}
+
+impl<T> Clone for PromiseStream<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().unwrap().senders += 1;
+        PromiseStream {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for PromiseStream<T> {
+    fn drop(&mut self) {
+        // Once the last sender goes away, wake the receiver so it observes
+        // the stream closing instead of waiting forever.
+        let mut shared = self.shared.lock().unwrap();
+        shared.senders -= 1;
+        if shared.senders == 0 {
+            shared.closed = true;
+            if let Some(waker) = shared.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// The receiving half of a [`PromiseStream`].
+pub struct FutureStream<T> {
+    shared: Arc<Mutex<StreamShared<T>>>,
+}
+
+/// Creates a fresh `PromiseStream`/`FutureStream` pair for actor-style
+/// multi-value messaging.
+pub fn promise_stream<T>() -> (PromiseStream<T>, FutureStream<T>) {
+    let shared = Arc::new(Mutex::new(StreamShared {
+        queue: VecDeque::new(),
+        waker: None,
+        closed: false,
+        senders: 1,
+    }));
+    (
+        PromiseStream {
+            shared: shared.clone(),
+        },
+        FutureStream { shared },
+    )
+}
+
+impl<T> PromiseStream<T> {
+    /// Pushes a value onto the stream, waking the receiver if it's waiting.
+    pub fn send(&self, value: T) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.queue.push_back(value);
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> FutureStream<T> {
+    /// Awaits the next value, or `None` once every [`PromiseStream`] sender
+    /// has been dropped and the queue has drained.
+    pub async fn next(&mut self) -> Option<T> {
+        let shared = &self.shared;
+        poll_fn(|cx| {
+            let mut shared = shared.lock().unwrap();
+            if let Some(value) = shared.queue.pop_front() {
+                Poll::Ready(Some(value))
+            } else if shared.closed {
+                Poll::Ready(None)
+            } else {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+/// Awaits the first of `futures` to become ready, returning its index and
+/// value. The rest are dropped (and so cancelled) once this future returns.
+///
+/// # Panics
+///
+/// Panics if `futures` is empty, rather than silently returning a future
+/// that's pending forever.
+pub async fn choose<T>(mut futures: Vec<Pin<Box<dyn StdFuture<Output = T> + Send>>>) -> (usize, T) {
+    assert!(
+        !futures.is_empty(),
+        "flow::choose called with no futures to select from"
+    );
+    poll_fn(move |cx| {
+        for (i, fut) in futures.iter_mut().enumerate() {
+            if let Poll::Ready(value) = fut.as_mut().poll(cx) {
+                return Poll::Ready((i, value));
+            }
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+/// The outcome of [`when`]: which of the two futures fired first, and its
+/// value.
+pub enum Selected<A, B> {
+    First(A),
+    Second(B),
+}
+
+/// Awaits whichever of `a` or `b` becomes ready first, dropping (and so
+/// cancelling) the other.
+pub async fn when<A, B>(a: A, b: B) -> Selected<A::Output, B::Output>
+where
+    A: StdFuture,
+    B: StdFuture,
+{
+    let mut a = Box::pin(a);
+    let mut b = Box::pin(b);
+    poll_fn(move |cx| {
+        if let Poll::Ready(value) = a.as_mut().poll(cx) {
+            return Poll::Ready(Selected::First(value));
+        }
+        if let Poll::Ready(value) = b.as_mut().poll(cx) {
+            return Poll::Ready(Selected::Second(value));
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+#[cfg(test)]
+mod choose_tests {
+    use super::*;
+    use std::future::{pending, ready};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn choose_returns_the_first_ready_future() {
+        let futures: Vec<Pin<Box<dyn StdFuture<Output = u32> + Send>>> =
+            vec![Box::pin(pending()), Box::pin(ready(42u32)), Box::pin(pending())];
+        assert_eq!(choose(futures).await, (1, 42));
+    }
+
+    #[tokio::test]
+    async fn choose_drops_the_futures_it_did_not_select() {
+        struct DropMarker(Arc<AtomicUsize>);
+        impl Drop for DropMarker {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let loser = {
+            let dropped = dropped.clone();
+            async move {
+                // Constructed on first poll, so the marker only fires if
+                // this future is actually polled (and then dropped while
+                // still suspended) rather than skipped entirely.
+                let _marker = DropMarker(dropped);
+                pending::<u32>().await
+            }
+        };
+        // The winner yields once before resolving, so both futures get
+        // polled at least once before `choose` picks a winner - otherwise
+        // `loser` would never even start, making "it was cancelled" an
+        // untested claim.
+        let winner = async {
+            tokio::task::yield_now().await;
+            7u32
+        };
+        let futures: Vec<Pin<Box<dyn StdFuture<Output = u32> + Send>>> =
+            vec![Box::pin(winner), Box::pin(loser)];
+
+        assert_eq!(choose(futures).await, (0, 7));
+        assert_eq!(
+            dropped.load(Ordering::SeqCst),
+            1,
+            "losing future was not cancelled"
+        );
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "flow::choose called with no futures to select from")]
+    async fn choose_panics_on_an_empty_vec() {
+        let futures: Vec<Pin<Box<dyn StdFuture<Output = ()> + Send>>> = Vec::new();
+        choose(futures).await;
+    }
+}
@@ -0,0 +1,323 @@
+//! The simulated backend: files live in a `HashMap<PathBuf, Vec<u8>>` instead
+//! of on disk, and connections are in-process channel pairs instead of
+//! sockets. Latency and faults (slow disk, disk-full, dropped packets) are
+//! driven by [`crate::flow::delay`] and [`crate::flow::buggify`], so they
+//! replay identically for a given simulation seed.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::{mpsc, Mutex as AsyncMutex};
+
+use super::{IAsyncFile, INetworkConnection};
+use crate::flow::{self, Error, Result};
+
+/// A shared simulated disk: every [`MemFile`] opened from the same `MemDisk`
+/// sees the same backing store, the way every file opened from the same real
+/// filesystem does.
+#[derive(Clone, Default)]
+pub struct MemDisk {
+    files: Arc<Mutex<HashMap<PathBuf, Vec<u8>>>>,
+}
+
+impl MemDisk {
+    pub fn new() -> Self {
+        MemDisk::default()
+    }
+
+    pub fn open(&self, path: impl Into<PathBuf>) -> MemFile {
+        let path = path.into();
+        self.files.lock().unwrap().entry(path.clone()).or_default();
+        MemFile {
+            path,
+            disk: self.clone(),
+        }
+    }
+}
+
+/// An [`IAsyncFile`] backed by [`MemDisk`]'s in-memory store.
+pub struct MemFile {
+    path: PathBuf,
+    disk: MemDisk,
+}
+
+impl MemFile {
+    /// A slow-disk fault: occasionally stall an operation before it runs.
+    async fn simulate_latency(&self) {
+        if flow::buggify(0.01) {
+            flow::delay(Duration::from_millis(100)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl IAsyncFile for MemFile {
+    async fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize> {
+        self.simulate_latency().await;
+        let files = self.disk.files.lock().unwrap();
+        let data = files
+            .get(&self.path)
+            .ok_or_else(|| Error::Other("file not open".into()))?;
+        let offset = offset as usize;
+        if offset >= data.len() {
+            return Ok(0);
+        }
+        let n = buf.len().min(data.len() - offset);
+        buf[..n].copy_from_slice(&data[offset..offset + n]);
+        Ok(n)
+    }
+
+    async fn write_at(&self, offset: u64, buf: &[u8]) -> Result<usize> {
+        self.simulate_latency().await;
+        // A disk-full fault: deterministically reject the write under sim.
+        if flow::buggify(0.01) {
+            return Err(Error::Other("simulated disk full".into()));
+        }
+        let mut files = self.disk.files.lock().unwrap();
+        let data = files
+            .get_mut(&self.path)
+            .ok_or_else(|| Error::Other("file not open".into()))?;
+        let offset = offset as usize;
+        if data.len() < offset + buf.len() {
+            data.resize(offset + buf.len(), 0);
+        }
+        data[offset..offset + buf.len()].copy_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    async fn sync(&self) -> Result<()> {
+        self.simulate_latency().await;
+        Ok(())
+    }
+
+    async fn truncate(&self, size: u64) -> Result<()> {
+        let mut files = self.disk.files.lock().unwrap();
+        let data = files
+            .get_mut(&self.path)
+            .ok_or_else(|| Error::Other("file not open".into()))?;
+        data.resize(size as usize, 0);
+        Ok(())
+    }
+
+    async fn size(&self) -> Result<u64> {
+        let files = self.disk.files.lock().unwrap();
+        let data = files
+            .get(&self.path)
+            .ok_or_else(|| Error::Other("file not open".into()))?;
+        Ok(data.len() as u64)
+    }
+}
+
+/// An [`INetworkConnection`] backed by an in-process channel pair. `connect`
+/// and `listen` are no-ops here since pairing happens at construction time
+/// via [`MemConnection::pair`]; they exist only to satisfy the trait so
+/// `fdbserver` code doesn't need to distinguish backends.
+pub struct MemConnection {
+    tx: mpsc::UnboundedSender<Vec<u8>>,
+    rx: AsyncMutex<mpsc::UnboundedReceiver<Vec<u8>>>,
+}
+
+impl MemConnection {
+    /// Builds a connected pair, the way a simulated client and server would
+    /// see each other after a handshake.
+    pub fn pair() -> (MemConnection, MemConnection) {
+        let (tx_a, rx_b) = mpsc::unbounded_channel();
+        let (tx_b, rx_a) = mpsc::unbounded_channel();
+        (
+            MemConnection {
+                tx: tx_a,
+                rx: AsyncMutex::new(rx_a),
+            },
+            MemConnection {
+                tx: tx_b,
+                rx: AsyncMutex::new(rx_b),
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl INetworkConnection for MemConnection {
+    async fn connect(&self, _addr: SocketAddr) -> Result<()> {
+        Ok(())
+    }
+
+    async fn listen(&self, _addr: SocketAddr) -> Result<()> {
+        Ok(())
+    }
+
+    async fn send(&self, buf: &[u8]) -> Result<usize> {
+        // A dropped-packet fault: deterministically swallow the send.
+        if flow::buggify(0.01) {
+            return Ok(buf.len());
+        }
+        self.tx
+            .send(buf.to_vec())
+            .map_err(|_| Error::Other("peer disconnected".into()))?;
+        Ok(buf.len())
+    }
+
+    async fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        let mut rx = self.rx.lock().await;
+        match rx.recv().await {
+            Some(data) => {
+                let n = buf.len().min(data.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                Ok(n)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn file_write_then_read_round_trips() {
+        let disk = MemDisk::new();
+        let file = disk.open("/foo");
+
+        file.write_at(0, b"hello world").await.unwrap();
+        let mut buf = [0u8; 5];
+        let n = file.read_at(6, &mut buf).await.unwrap();
+        assert_eq!(n, 5);
+        assert_eq!(&buf, b"world");
+        assert_eq!(file.size().await.unwrap(), 11);
+    }
+
+    #[tokio::test]
+    async fn file_read_past_end_returns_zero() {
+        let disk = MemDisk::new();
+        let file = disk.open("/foo");
+        file.write_at(0, b"hi").await.unwrap();
+
+        let mut buf = [0u8; 4];
+        let n = file.read_at(10, &mut buf).await.unwrap();
+        assert_eq!(n, 0);
+    }
+
+    #[tokio::test]
+    async fn file_truncate_shrinks_and_zero_fills() {
+        let disk = MemDisk::new();
+        let file = disk.open("/foo");
+        file.write_at(0, b"hello world").await.unwrap();
+
+        file.truncate(5).await.unwrap();
+        assert_eq!(file.size().await.unwrap(), 5);
+
+        file.truncate(8).await.unwrap();
+        let mut buf = [0u8; 8];
+        file.read_at(0, &mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello\0\0\0");
+    }
+
+    #[tokio::test]
+    async fn files_opened_from_the_same_disk_share_storage() {
+        let disk = MemDisk::new();
+        let writer = disk.open("/shared");
+        let reader = disk.open("/shared");
+
+        writer.write_at(0, b"shared bytes").await.unwrap();
+        let mut buf = [0u8; 12];
+        reader.read_at(0, &mut buf).await.unwrap();
+        assert_eq!(&buf, b"shared bytes");
+    }
+
+    #[tokio::test]
+    async fn connection_pair_round_trips_in_both_directions() {
+        let (a, b) = MemConnection::pair();
+
+        a.send(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        let n = b.recv(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+
+        b.send(b"pong").await.unwrap();
+        let n = a.recv(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"pong");
+    }
+
+    // The three fault hooks below (`simulate_latency`, `write_at`'s
+    // disk-full check, `send`'s dropped-packet check) only ever fire inside
+    // `Sim::run_with_seed`: outside simulation `flow::buggify` is a hardcoded
+    // `false`. The plain `#[tokio::test]`s above never run under `Sim`, so
+    // they can't exercise any of these paths. Each test here loops the
+    // faulty call enough times under a fixed seed to hit the 1% draw at
+    // least once and asserts the caller actually sees the documented
+    // failure, rather than trusting that the branch is reachable.
+    const FAULT_ATTEMPTS: u32 = 2000;
+
+    #[test]
+    fn slow_disk_fault_stalls_an_operation_under_sim() {
+        let found = crate::flow::sim::Sim::run_with_seed(1, async {
+            let disk = MemDisk::new();
+            let file = disk.open("/foo");
+            for _ in 0..FAULT_ATTEMPTS {
+                let before = crate::flow::now();
+                file.sync().await.unwrap();
+                if crate::flow::now() - before >= Duration::from_millis(100).as_micros() as u64 {
+                    return true;
+                }
+            }
+            false
+        })
+        .expect("root future completes");
+        assert!(
+            found,
+            "simulate_latency never stalled an op in {FAULT_ATTEMPTS} attempts"
+        );
+    }
+
+    #[test]
+    fn write_at_disk_full_fault_returns_err_under_sim() {
+        let found = crate::flow::sim::Sim::run_with_seed(2, async {
+            let disk = MemDisk::new();
+            let file = disk.open("/foo");
+            for _ in 0..FAULT_ATTEMPTS {
+                if file.write_at(0, b"x").await.is_err() {
+                    return true;
+                }
+            }
+            false
+        })
+        .expect("root future completes");
+        assert!(
+            found,
+            "write_at's disk-full fault never returned Err in {FAULT_ATTEMPTS} attempts"
+        );
+    }
+
+    #[test]
+    fn send_dropped_packet_fault_silently_swallows_under_sim() {
+        use crate::flow::{self, Selected};
+
+        let found = crate::flow::sim::Sim::run_with_seed(3, async {
+            for _ in 0..FAULT_ATTEMPTS {
+                let (a, b) = MemConnection::pair();
+                a.send(b"x").await.unwrap();
+
+                let mut buf = [0u8; 1];
+                let recv = b.recv(&mut buf);
+                let timeout = flow::delay(Duration::from_millis(1));
+                // A successful send delivers instantly (no latency fault on
+                // this path), so `recv` only loses the race when the packet
+                // was dropped and never actually queued.
+                if matches!(flow::when(recv, timeout).await, Selected::Second(())) {
+                    return true;
+                }
+            }
+            false
+        })
+        .expect("root future completes");
+        assert!(
+            found,
+            "send's dropped-packet fault never swallowed a send in {FAULT_ATTEMPTS} attempts"
+        );
+    }
+}
@@ -0,0 +1,34 @@
+//! A facade over disk and network I/O, so server code can be written once and
+//! run either against the real OS (the `tokio` backend) or against the
+//! deterministic simulator (the `mem` backend), with the same trait objects
+//! threaded through `fdbserver` either way.
+
+pub mod mem;
+pub mod tokio_backend;
+
+use std::net::SocketAddr;
+
+use async_trait::async_trait;
+
+use crate::flow::Result;
+
+/// An async file handle. Implementations may back onto the real filesystem
+/// or an in-memory buffer driven by the simulator's virtual clock.
+#[async_trait]
+pub trait IAsyncFile: Send + Sync {
+    async fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize>;
+    async fn write_at(&self, offset: u64, buf: &[u8]) -> Result<usize>;
+    async fn sync(&self) -> Result<()>;
+    async fn truncate(&self, size: u64) -> Result<()>;
+    async fn size(&self) -> Result<u64>;
+}
+
+/// An async network connection. Implementations may back onto real TCP
+/// sockets or an in-process channel pair driven by the simulator.
+#[async_trait]
+pub trait INetworkConnection: Send + Sync {
+    async fn connect(&self, addr: SocketAddr) -> Result<()>;
+    async fn listen(&self, addr: SocketAddr) -> Result<()>;
+    async fn send(&self, buf: &[u8]) -> Result<usize>;
+    async fn recv(&self, buf: &mut [u8]) -> Result<usize>;
+}
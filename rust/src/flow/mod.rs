@@ -0,0 +1,103 @@
+//! Flow is FoundationDB's cooperative actor runtime. This crate's port starts
+//! small: a shared error type, a couple of runtime-agnostic helpers, and the
+//! deterministic simulation scheduler in [`sim`] that everything else in
+//! `fdbserver` is built to run under.
+
+use std::fmt;
+use std::time::Duration;
+
+pub mod actor;
+pub mod io;
+pub mod sim;
+
+pub use actor::{
+    choose, promise, promise_stream, when, Cancelled, Future, FutureStream, Promise,
+    PromiseStream, Selected,
+};
+
+/// Errors produced by the flow runtime and the services built on top of it.
+///
+/// Stores I/O failures as their rendered message rather than the original
+/// `std::io::Error` so that `Result<T, Error>` stays `Clone`, which lets a
+/// [`actor::Promise`]'s result fan out to every [`actor::Future`] awaiting it.
+#[derive(Debug, Clone)]
+pub enum Error {
+    Io(String),
+    Other(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "io error: {e}"),
+            Error::Other(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e.to_string())
+    }
+}
+
+impl From<Cancelled> for Error {
+    fn from(e: Cancelled) -> Self {
+        Error::Other(e.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The current time, in microseconds: virtual simulation time if the calling
+/// task is running under [`sim::Sim::run_with_seed`], otherwise real wall-clock
+/// time.
+pub fn now() -> u64 {
+    sim::with_current(|s| s.now()).unwrap_or_else(|| {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64
+    })
+}
+
+/// Suspends the calling task for `d`. Under simulation this advances the
+/// virtual clock instead of sleeping in real time, so a whole cluster's worth
+/// of timers can be fast-forwarded deterministically.
+pub async fn delay(d: Duration) {
+    if sim::is_active() {
+        sim::Delay::new(d).await
+    } else {
+        tokio::time::sleep(d).await
+    }
+}
+
+/// A fault-injection hook. Outside of simulation this always returns `false`;
+/// under [`sim::Sim`] it returns `true` with probability `probability`, drawn
+/// from the simulation's seeded RNG, so that a given seed always trips (or
+/// doesn't trip) the same buggify points.
+pub fn buggify(probability: f64) -> bool {
+    sim::with_current(|s| s.buggify(probability)).unwrap_or(false)
+}
+
+/// Spawns `fut` as a concurrent task. Under simulation it's driven by the
+/// active [`sim::Sim`] alongside everything else the current seed controls;
+/// otherwise it's handed to `tokio::spawn` like any other background task.
+pub fn spawn<F>(fut: F)
+where
+    F: std::future::Future<Output = ()> + Send + 'static,
+{
+    if sim::is_active() {
+        sim::spawn(fut);
+    } else {
+        tokio::spawn(fut);
+    }
+}
+
+pub async fn hello() -> Result<()> {
+    println!("Hello from flow!");
+    Ok(())
+}
@@ -0,0 +1,385 @@
+//! A single-threaded, deterministic discrete-event scheduler.
+//!
+//! [`Sim`] owns a virtual clock, a priority queue of `(virtual_time,
+//! tie_break, waker)` events, a slab of concurrently-spawned tasks, and two
+//! independently-seeded PRNG streams: one drives scheduling decisions (timer
+//! tie-breaks, which ready task runs next) and the other drives
+//! [`super::buggify`] fault injection, so a change to one never perturbs the
+//! other. Tasks never touch wall-clock time or a real thread pool:
+//! [`super::delay`] schedules a wakeup against the virtual clock instead of
+//! sleeping. The result is bit-for-bit reproducibility: the same seed, run
+//! against the same code, always produces the same event interleaving, so a
+//! failing run can be replayed.
+//!
+//! `fdbserver` roles that need to run concurrently with the task driving
+//! them (e.g. a background batching loop) use [`super::spawn`] rather than
+//! `tokio::spawn`, so they're scheduled through this executor instead of
+//! requiring a live Tokio reactor.
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<Sim>>> = const { RefCell::new(None) };
+}
+
+type BoxedTask = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+struct Scheduled {
+    time: u64,
+    // A random tie-breaker drawn from the sim's seeded RNG at schedule
+    // time, *not* a monotonic counter: two timers landing on the same
+    // virtual `time` must resolve in an order that depends on the seed,
+    // or sweeping seeds would never explore a different interleaving.
+    tie_break: u64,
+    waker: Waker,
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        self.time == other.time && self.tie_break == other.tie_break
+    }
+}
+
+impl Eq for Scheduled {}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scheduled {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap; reverse the key so the earliest
+        // (time, tie_break) pair is always popped first.
+        (other.time, other.tie_break).cmp(&(self.time, self.tie_break))
+    }
+}
+
+struct Inner {
+    now: u64,
+    timers: BinaryHeap<Scheduled>,
+    // A slab of spawned tasks; `None` marks a freed (completed) slot.
+    tasks: Vec<Option<BoxedTask>>,
+    ready: VecDeque<usize>,
+    // Drives scheduling decisions (timer tie-breaks, ready-queue pops).
+    rng: StdRng,
+    // A separate stream for `buggify()` draws, seeded from the same seed
+    // but never touched by scheduling: if the two shared a stream, any
+    // change elsewhere in the sim that adds or removes a scheduling draw
+    // ahead of a `buggify()` call would shift it onto a different random
+    // value, silently flipping an unrelated, already-passing test.
+    buggify_rng: StdRng,
+    self_weak: Weak<Sim>,
+}
+
+/// Deterministic discrete-event runtime. Construct with [`Sim::run_with_seed`].
+pub struct Sim {
+    inner: Mutex<Inner>,
+}
+
+impl Sim {
+    /// Spawns `fut` on a fresh simulation seeded with `seed` and drives every
+    /// task spawned from it (via [`super::spawn`]) to quiescence, returning
+    /// `fut`'s output, or `None` if the event queue drains while it's still
+    /// pending.
+    pub fn run_with_seed<F>(seed: u64, fut: F) -> Option<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        // Derived from `seed` rather than sharing `rng`'s stream, so
+        // scheduling draws and fault-injection draws can never compete for
+        // the same random values.
+        let mut rng = StdRng::seed_from_u64(seed);
+        let buggify_rng = StdRng::seed_from_u64(rng.gen());
+
+        let sim = Arc::new(Sim {
+            inner: Mutex::new(Inner {
+                now: 0,
+                timers: BinaryHeap::new(),
+                tasks: Vec::new(),
+                ready: VecDeque::new(),
+                rng,
+                buggify_rng,
+                self_weak: Weak::new(),
+            }),
+        });
+        sim.inner.lock().unwrap().self_weak = Arc::downgrade(&sim);
+
+        let result = Arc::new(Mutex::new(None));
+        let result_slot = result.clone();
+        sim.spawn_boxed(Box::pin(async move {
+            *result_slot.lock().unwrap() = Some(fut.await);
+        }));
+
+        CURRENT.with(|c| *c.borrow_mut() = Some(sim.clone()));
+        sim.drive();
+        CURRENT.with(|c| *c.borrow_mut() = None);
+
+        let output = result.lock().unwrap().take();
+        output
+    }
+
+    fn spawn_boxed(&self, fut: BoxedTask) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.tasks.push(Some(fut));
+        let id = inner.tasks.len() - 1;
+        inner.ready.push_back(id);
+    }
+
+    /// Polls ready tasks and advances the virtual clock to the next timer
+    /// until no task is runnable and no timer is pending.
+    fn drive(&self) {
+        loop {
+            // Each lookup is pulled into its own binding rather than matched
+            // on directly: a `MutexGuard` produced in a `match` scrutinee
+            // stays alive for the whole match (including other arms), which
+            // would deadlock against the second `lock()` below.
+            let ready_id = self.pop_random_ready();
+            let id = match ready_id {
+                Some(id) => id,
+                None => {
+                    let timer = self.inner.lock().unwrap().timers.pop();
+                    match timer {
+                        Some(sch) => {
+                            let mut inner = self.inner.lock().unwrap();
+                            inner.now = inner.now.max(sch.time);
+                            drop(inner);
+                            sch.waker.wake();
+                            continue;
+                        }
+                        None => return,
+                    }
+                }
+            };
+
+            let task = self.inner.lock().unwrap().tasks[id].take();
+            let Some(mut task) = task else { continue };
+
+            let self_weak = self.inner.lock().unwrap().self_weak.clone();
+            let waker = Waker::from(Arc::new(TaskWaker { sim: self_weak, id }));
+            let mut cx = Context::from_waker(&waker);
+            if task.as_mut().poll(&mut cx).is_pending() {
+                self.inner.lock().unwrap().tasks[id] = Some(task);
+            }
+        }
+    }
+
+    /// Removes one task at random from the ready queue rather than always
+    /// taking the front: when several tasks become runnable at the same
+    /// virtual time, which one runs first must depend on the seed, or every
+    /// seed would replay the same interleaving.
+    fn pop_random_ready(&self) -> Option<usize> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.ready.is_empty() {
+            return None;
+        }
+        let len = inner.ready.len();
+        let index = inner.rng.gen_range(0..len);
+        inner.ready.remove(index)
+    }
+
+    pub(crate) fn now(&self) -> u64 {
+        self.inner.lock().unwrap().now
+    }
+
+    pub(crate) fn schedule(&self, at: u64, waker: Waker) {
+        let mut inner = self.inner.lock().unwrap();
+        let tie_break = inner.rng.gen();
+        inner.timers.push(Scheduled {
+            time: at,
+            tie_break,
+            waker,
+        });
+    }
+
+    pub(crate) fn buggify(&self, probability: f64) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .buggify_rng
+            .gen_bool(probability.clamp(0.0, 1.0))
+    }
+}
+
+/// Wakes the simulation's executor to re-poll task `id` rather than calling
+/// back into whatever thread happened to fire the wakeup.
+struct TaskWaker {
+    sim: Weak<Sim>,
+    id: usize,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        if let Some(sim) = self.sim.upgrade() {
+            sim.inner.lock().unwrap().ready.push_back(self.id);
+        }
+    }
+}
+
+pub(crate) fn is_active() -> bool {
+    CURRENT.with(|c| c.borrow().is_some())
+}
+
+pub(crate) fn with_current<T>(f: impl FnOnce(&Sim) -> T) -> Option<T> {
+    CURRENT.with(|c| c.borrow().as_ref().map(|s| f(s)))
+}
+
+/// Spawns `fut` as a new task on the active simulation. Does nothing outside
+/// of simulation; callers go through [`super::spawn`], which falls back to
+/// `tokio::spawn` in that case.
+pub(crate) fn spawn<F>(fut: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    with_current(|sim| sim.spawn_boxed(Box::pin(fut)));
+}
+
+/// A future that resolves once the active [`Sim`]'s virtual clock reaches
+/// `now + d`. Only constructed from [`super::delay`] when running under
+/// simulation.
+pub(crate) struct Delay {
+    wake_at: u64,
+    registered: bool,
+}
+
+impl Delay {
+    pub(crate) fn new(d: Duration) -> Self {
+        let wake_at = with_current(|s| s.now()).unwrap_or(0) + d.as_micros() as u64;
+        Delay {
+            wake_at,
+            registered: false,
+        }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        with_current(|sim| {
+            if sim.now() >= this.wake_at {
+                Poll::Ready(())
+            } else {
+                if !this.registered {
+                    sim.schedule(this.wake_at, cx.waker().clone());
+                    this.registered = true;
+                }
+                Poll::Pending
+            }
+        })
+        .unwrap_or(Poll::Ready(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::flow::actor::Selected;
+
+    /// Races two delays of different lengths plus a handful of `buggify`
+    /// draws through the same seed twice. If the scheduler is truly
+    /// deterministic, both runs must produce identical output.
+    fn race_and_collect(seed: u64) -> Vec<u64> {
+        super::Sim::run_with_seed(seed, async {
+            let mut trace = Vec::new();
+
+            let short = async {
+                crate::flow::delay(Duration::from_millis(1)).await;
+                1u64
+            };
+            let long = async {
+                crate::flow::delay(Duration::from_millis(5)).await;
+                2u64
+            };
+            match crate::flow::when(short, long).await {
+                Selected::First(v) | Selected::Second(v) => trace.push(v),
+            }
+
+            trace.push(crate::flow::now());
+            for _ in 0..5 {
+                trace.push(crate::flow::buggify(0.5) as u64);
+            }
+            trace
+        })
+        .expect("future completes well before the event queue drains")
+    }
+
+    #[test]
+    fn same_seed_reproduces_identical_interleaving() {
+        let first = race_and_collect(42);
+        let second = race_and_collect(42);
+        assert_eq!(first, second);
+
+        // The shorter delay should always win the race.
+        assert_eq!(first[0], 1);
+    }
+
+    #[test]
+    fn buggify_is_inert_outside_simulation() {
+        assert!(!crate::flow::buggify(1.0));
+    }
+
+    /// Spawns several tasks that all wake at the *same* virtual time and
+    /// records the order they actually ran in. Different seeds must be free
+    /// to resolve that tie differently, or sweeping seeds would never
+    /// explore a new interleaving.
+    fn race_same_instant(seed: u64) -> Vec<u64> {
+        super::Sim::run_with_seed(seed, async move {
+            let trace = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+            for i in 0..6u64 {
+                let trace = trace.clone();
+                crate::flow::spawn(async move {
+                    crate::flow::delay(Duration::from_millis(1)).await;
+                    trace.lock().unwrap().push(i);
+                });
+            }
+            crate::flow::delay(Duration::from_millis(2)).await;
+            std::sync::Arc::try_unwrap(trace).unwrap().into_inner().unwrap()
+        })
+        .expect("future completes well before the event queue drains")
+    }
+
+    #[test]
+    fn varying_the_seed_varies_the_interleaving() {
+        // Same seed must always reproduce the same order...
+        assert_eq!(race_same_instant(1), race_same_instant(1));
+
+        // ...but sweeping seeds must eventually produce a different one:
+        // a scheduler that only ever resolves same-time ties in program
+        // order would fail this no matter how many seeds we tried.
+        let baseline = race_same_instant(0);
+        let found_different = (1..50).any(|seed| race_same_instant(seed) != baseline);
+        assert!(
+            found_different,
+            "every seed from 0..50 produced the same interleaving \
+             ({baseline:?}); tie-breaking isn't using the RNG"
+        );
+    }
+
+    #[test]
+    fn spawned_task_runs_concurrently_with_the_root_future() {
+        let (promise, future) = crate::flow::promise();
+        let seen = super::Sim::run_with_seed(7, async move {
+            crate::flow::spawn(async move {
+                crate::flow::delay(Duration::from_millis(1)).await;
+                promise.send(99u64);
+            });
+            future.await
+        });
+        assert_eq!(seen, Some(Ok(99)));
+    }
+}
@@ -0,0 +1,8 @@
+//! The `foundationdb` crate: a `flow`-based client, the `fdbserver` roles
+//! built on it, and the `flow` runtime itself. Structured as a library with a
+//! thin `main.rs` demo binary on top, so unit tests and any future
+//! integration tests exercise the same public API the binary does.
+
+pub mod client;
+pub mod fdbserver;
+pub mod flow;
@@ -1,9 +1,20 @@
-mod flow;
-mod fdbserver;
+use std::sync::Arc;
+use std::time::Duration;
+
+use foundationdb::fdbserver::grv_master::{GrvMaster, GrvMasterConfig, Priority};
+use foundationdb::flow;
+use foundationdb::flow::io::tokio_backend::TokioConnection;
 
 #[tokio::main]
 async fn main() -> flow::Result<()> {
-    fdbserver::grv_master::foo();
+    let grv = GrvMaster::new(GrvMasterConfig {
+        network: Arc::new(TokioConnection::new()),
+        batch_window: Duration::from_millis(1),
+        max_batch_size: 1000,
+    });
+    let version = grv.get_read_version(Priority::Default).await?;
+    println!("got read version {version}");
+
     flow::hello().await?;
     println!("Goodbye, cruel world!");
 